@@ -0,0 +1,270 @@
+use envelope::Envelope;
+use node::{ParentNodeData, RTreeNode};
+use object::{PointDistance, RTreeObject};
+use params::RTreeParams;
+use point::Point;
+use rtree::RTree;
+use std::collections::HashMap;
+
+type Scalar<T> = <<<T as RTreeObject>::Envelope as Envelope>::Point as Point>::Scalar;
+
+/// Finds the nearest object in `self_root` for every object stored in
+/// `other_root` by walking both trees simultaneously instead of repeating an
+/// independent `nearest_neighbor` descent per query object.
+///
+/// Node pairs are explored in order of their envelope-to-envelope distance,
+/// and a pair is skipped once that distance exceeds the best match already
+/// found for every query object it could possibly improve on. This amortizes
+/// traversal across all queries, which is much cheaper than `N` independent
+/// descents for a spatial join between two trees.
+pub fn nearest_neighbors_for_tree<'a, 'b, T, Params>(
+    self_root: &'a ParentNodeData<T, Params>,
+    other_root: &'b ParentNodeData<T, Params>,
+) -> Vec<(&'b T, Option<&'a T>)>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    let mut best: HashMap<usize, (Scalar<T>, Option<&'a T>)> = HashMap::new();
+    let mut other_leaves: Vec<&'b T> = Vec::new();
+    collect_leaves(other_root, &mut other_leaves);
+
+    recurse_parent_pair(self_root, other_root, &mut best);
+
+    other_leaves
+        .into_iter()
+        .map(|other| {
+            let key = other as *const T as usize;
+            let nearest = best.get(&key).and_then(|&(_, nearest)| nearest);
+            (other, nearest)
+        })
+        .collect()
+}
+
+fn collect_leaves<'b, T, Params>(node: &'b ParentNodeData<T, Params>, leaves: &mut Vec<&'b T>)
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    for child in &node.children {
+        match child {
+            RTreeNode::Parent(ref data) => collect_leaves(data, leaves),
+            RTreeNode::Leaf(ref t) => leaves.push(t),
+        }
+    }
+}
+
+fn recurse<'a, 'b, T, Params>(
+    self_node: &'a RTreeNode<T, Params>,
+    other_node: &'b RTreeNode<T, Params>,
+    best: &mut HashMap<usize, (Scalar<T>, Option<&'a T>)>,
+) where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    match (self_node, other_node) {
+        (&RTreeNode::Leaf(ref self_leaf), &RTreeNode::Leaf(ref other_leaf)) => {
+            let distance = self_leaf.distance_2(&other_leaf.envelope().center());
+            update_best(best, other_leaf, distance, self_leaf);
+        }
+        (&RTreeNode::Leaf(_), &RTreeNode::Parent(ref other_data)) => {
+            for other_child in &other_data.children {
+                recurse(self_node, other_child, best);
+            }
+        }
+        (&RTreeNode::Parent(ref self_data), &RTreeNode::Leaf(ref other_leaf)) => {
+            let query_point = other_leaf.envelope().center();
+            if envelope_distance_2(&self_data.envelope, &other_node.envelope())
+                >= current_best(best, other_leaf)
+            {
+                return;
+            }
+            for self_child in &self_data.children {
+                let distance_bound = self_child.envelope().distance_2(&query_point);
+                if distance_bound < current_best(best, other_leaf) {
+                    recurse(self_child, other_node, best);
+                }
+            }
+        }
+        (&RTreeNode::Parent(ref self_data), &RTreeNode::Parent(ref other_data)) => {
+            recurse_parent_pair(self_data, other_data, best);
+        }
+    }
+}
+
+/// Recurses over every pair of children from two parent nodes, visiting the
+/// pairs whose envelopes are closest together first so that bounds tighten
+/// as early as possible.
+///
+/// A pair is dropped before it's ever visited once its envelope-to-envelope
+/// distance already exceeds the loosest current best among the leaves
+/// `other_child` could contain: nothing in `self_child` could still improve
+/// any of them. That bound is computed once per `other_child` via
+/// [`subtree_worst_bound`] rather than once per pair, since it only depends
+/// on `other_child`.
+fn recurse_parent_pair<'a, 'b, T, Params>(
+    self_data: &'a ParentNodeData<T, Params>,
+    other_data: &'b ParentNodeData<T, Params>,
+    best: &mut HashMap<usize, (Scalar<T>, Option<&'a T>)>,
+) where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    let other_bounds: Vec<_> = other_data
+        .children
+        .iter()
+        .map(|other_child| subtree_worst_bound(other_child, best))
+        .collect();
+
+    let mut pairs: Vec<_> = self_data
+        .children
+        .iter()
+        .flat_map(|self_child| {
+            other_data
+                .children
+                .iter()
+                .zip(other_bounds.iter())
+                .filter_map(move |(other_child, &bound)| {
+                    let distance = envelope_distance_2(&self_child.envelope(), &other_child.envelope());
+                    if distance < bound {
+                        Some((self_child, other_child, distance))
+                    } else {
+                        None
+                    }
+                })
+        })
+        .collect();
+    pairs.sort_by(|&(_, _, a_distance), &(_, _, b_distance)| {
+        a_distance.partial_cmp(&b_distance).unwrap()
+    });
+    for (self_child, other_child, _) in pairs {
+        recurse(self_child, other_child, best);
+    }
+}
+
+/// The loosest (largest) current best distance among the leaves under
+/// `node`, or `Bounded::max_value()` for any leaf under it that is still
+/// unvisited. A `(self_child, other_child)` pair can only improve a leaf
+/// under `other_child` if its envelope distance beats this bound, so it
+/// conservatively caps how useful `other_child` still is.
+fn subtree_worst_bound<'a, T, Params>(
+    node: &RTreeNode<T, Params>,
+    best: &HashMap<usize, (Scalar<T>, Option<&'a T>)>,
+) -> Scalar<T>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    use num_traits::Zero;
+    match *node {
+        RTreeNode::Leaf(ref t) => current_best(best, t),
+        RTreeNode::Parent(ref data) => data
+            .children
+            .iter()
+            .map(|child| subtree_worst_bound(child, best))
+            .fold(Zero::zero(), |worst, bound| if bound > worst { bound } else { worst }),
+    }
+}
+
+fn current_best<'a, T>(best: &HashMap<usize, (Scalar<T>, Option<&'a T>)>, other_leaf: &T) -> Scalar<T>
+where
+    T: PointDistance,
+{
+    use num_traits::Bounded;
+    let key = other_leaf as *const T as usize;
+    best.get(&key)
+        .map(|&(distance, _)| distance)
+        .unwrap_or_else(Bounded::max_value)
+}
+
+fn update_best<'a, T>(
+    best: &mut HashMap<usize, (Scalar<T>, Option<&'a T>)>,
+    other_leaf: &T,
+    distance: Scalar<T>,
+    candidate: &'a T,
+) where
+    T: PointDistance,
+{
+    let key = other_leaf as *const T as usize;
+    let better = match best.get(&key) {
+        Some(&(best_distance, _)) => distance < best_distance,
+        None => true,
+    };
+    if better {
+        best.insert(key, (distance, Some(candidate)));
+    }
+}
+
+fn envelope_distance_2<E>(a: &E, b: &E) -> <E::Point as Point>::Scalar
+where
+    E: Envelope,
+{
+    use num_traits::Zero;
+    let (a_lower, a_upper) = (a.lower(), a.upper());
+    let (b_lower, b_upper) = (b.lower(), b.upper());
+    let mut sum = Zero::zero();
+    for axis in 0..E::Point::dimensions() {
+        let (a_lo, a_hi) = (a_lower.nth(axis), a_upper.nth(axis));
+        let (b_lo, b_hi) = (b_lower.nth(axis), b_upper.nth(axis));
+        let gap = if a_hi < b_lo {
+            b_lo - a_hi
+        } else if b_hi < a_lo {
+            a_lo - b_hi
+        } else {
+            Zero::zero()
+        };
+        sum = sum + gap * gap;
+    }
+    sum
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    /// Finds the nearest object in `self` for every object stored in
+    /// `other`; see [`nearest_neighbors_for_tree`] for details.
+    pub fn nearest_neighbors_for_tree<'a, 'b>(
+        &'a self,
+        other: &'b RTree<T, Params>,
+    ) -> Vec<(&'b T, Option<&'a T>)> {
+        nearest_neighbors_for_tree(self.root(), other.root())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use object::PointDistance;
+    use rtree::RTree;
+    use testutils::create_random_points;
+
+    #[test]
+    fn test_nearest_neighbors_for_tree_empty() {
+        let self_tree: RTree<[f32; 2]> = RTree::new();
+        let other_tree: RTree<[f32; 2]> = RTree::new();
+        assert!(self_tree.nearest_neighbors_for_tree(&other_tree).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_neighbors_for_tree_matches_independent_queries() {
+        let self_points = create_random_points(500, *b"dualTreeSelfFren");
+        let other_points = create_random_points(200, *b"dualTreeOtherFrn");
+
+        let mut self_tree = RTree::new();
+        for p in &self_points {
+            self_tree.insert(*p);
+        }
+        let mut other_tree = RTree::new();
+        for p in &other_points {
+            other_tree.insert(*p);
+        }
+
+        let joined = self_tree.nearest_neighbors_for_tree(&other_tree);
+        assert_eq!(joined.len(), other_points.len());
+        for (other, nearest) in joined {
+            let expected = self_tree.nearest_neighbor(&other.envelope().center());
+            assert_eq!(expected, nearest);
+        }
+    }
+}
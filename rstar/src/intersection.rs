@@ -0,0 +1,198 @@
+use envelope::Envelope;
+use node::{ParentNodeData, RTreeNode};
+use object::RTreeObject;
+use params::RTreeParams;
+use rtree::RTree;
+
+/// An iterator over all objects whose envelope intersects (or is contained
+/// by, depending on `contains_only`) a query envelope.
+///
+/// This is the classic R-tree window query: starting from the root, any
+/// child whose envelope doesn't overlap the query envelope is pruned, and
+/// every leaf that passes the test is yielded.
+pub struct IntersectionIterator<'a, T, Params>
+where
+    T: RTreeObject + 'a,
+    Params: RTreeParams + 'a,
+{
+    query_envelope: T::Envelope,
+    contains_only: bool,
+    stack: Vec<&'a RTreeNode<T, Params>>,
+}
+
+impl<'a, T, Params> IntersectionIterator<'a, T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    fn new(root: &'a ParentNodeData<T, Params>, query_envelope: T::Envelope, contains_only: bool) -> Self {
+        let stack = root
+            .children
+            .iter()
+            .filter(|child| child_passes(child, &query_envelope, contains_only))
+            .collect();
+        IntersectionIterator {
+            query_envelope: query_envelope,
+            contains_only: contains_only,
+            stack: stack,
+        }
+    }
+}
+
+impl<'a, T, Params> Iterator for IntersectionIterator<'a, T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(current) = self.stack.pop() {
+            match current {
+                RTreeNode::Parent(ref data) => {
+                    self.stack.extend(data.children.iter().filter(|child| {
+                        child_passes(child, &self.query_envelope, self.contains_only)
+                    }));
+                }
+                RTreeNode::Leaf(ref t) => {
+                    return Some(t);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Decides whether `child` should be pushed onto the descent stack.
+///
+/// Parents are only ever pruned by intersection, even when `contains_only`
+/// is set: a parent whose envelope merely overlaps the query can still hold
+/// leaves that are fully contained within it. Containment is a leaf-only
+/// test.
+fn child_passes<T, Params>(
+    child: &RTreeNode<T, Params>,
+    query_envelope: &T::Envelope,
+    contains_only: bool,
+) -> bool
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    match *child {
+        RTreeNode::Parent(ref data) => data.envelope.intersects(query_envelope),
+        RTreeNode::Leaf(ref t) => {
+            let envelope = t.envelope();
+            if contains_only {
+                query_envelope.contains_envelope(&envelope)
+            } else {
+                envelope.intersects(query_envelope)
+            }
+        }
+    }
+}
+
+/// Returns every object in the tree rooted at `root` that is fully contained
+/// within `query_envelope`.
+pub fn locate_in_envelope<'a, T, Params>(
+    root: &'a ParentNodeData<T, Params>,
+    query_envelope: &T::Envelope,
+) -> IntersectionIterator<'a, T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    IntersectionIterator::new(root, query_envelope.clone(), true)
+}
+
+/// Returns every object in the tree rooted at `root` whose envelope merely
+/// intersects `query_envelope`, including objects that only touch its
+/// boundary.
+pub fn locate_in_envelope_intersecting<'a, T, Params>(
+    root: &'a ParentNodeData<T, Params>,
+    query_envelope: &T::Envelope,
+) -> IntersectionIterator<'a, T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    IntersectionIterator::new(root, query_envelope.clone(), false)
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Returns every stored object fully contained within `query_envelope`;
+    /// see [`locate_in_envelope`] for details.
+    pub fn locate_in_envelope(&self, query_envelope: &T::Envelope) -> IntersectionIterator<T, Params> {
+        locate_in_envelope(self.root(), query_envelope)
+    }
+
+    /// Returns every stored object whose envelope intersects
+    /// `query_envelope`; see [`locate_in_envelope_intersecting`] for details.
+    pub fn locate_in_envelope_intersecting(
+        &self,
+        query_envelope: &T::Envelope,
+    ) -> IntersectionIterator<T, Params> {
+        locate_in_envelope_intersecting(self.root(), query_envelope)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use envelope::Envelope;
+    use object::RTreeObject;
+    use rtree::RTree;
+    use testutils::create_random_points;
+
+    #[test]
+    fn test_locate_in_envelope_empty() {
+        let tree: RTree<[f32; 2]> = RTree::new();
+        let query = [0.0, 0.0].envelope().merged(&[10.0, 10.0].envelope());
+        assert_eq!(tree.locate_in_envelope(&query).count(), 0);
+    }
+
+    #[test]
+    fn test_locate_in_envelope_matches_brute_force() {
+        let points = create_random_points(1000, *b"windowQueryFren!");
+        let mut tree = RTree::new();
+        for p in &points {
+            tree.insert(*p);
+        }
+        let query = [20.0, 20.0].envelope().merged(&[60.0, 60.0].envelope());
+
+        let mut expected: Vec<_> = points
+            .iter()
+            .filter(|p| query.contains_point(p))
+            .cloned()
+            .collect();
+        let mut actual: Vec<_> = tree.locate_in_envelope(&query).cloned().collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        actual.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_locate_in_envelope_intersecting_matches_brute_force() {
+        let points = create_random_points(1000, *b"intersectQueryFr");
+        let mut tree = RTree::new();
+        for p in &points {
+            tree.insert(*p);
+        }
+        let query = [20.0, 20.0].envelope().merged(&[60.0, 60.0].envelope());
+
+        let mut expected: Vec<_> = points
+            .iter()
+            .filter(|p| p.envelope().intersects(&query))
+            .cloned()
+            .collect();
+        let mut actual: Vec<_> = tree
+            .locate_in_envelope_intersecting(&query)
+            .cloned()
+            .collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        actual.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(expected, actual);
+    }
+}
@@ -6,13 +6,13 @@ use params::RTreeParams;
 use point::{min_inline, Point};
 use std::collections::binary_heap::BinaryHeap;
 
-struct RTreeNodeDistanceWrapper<'a, T, Params>
+pub(crate) struct RTreeNodeDistanceWrapper<'a, T, Params>
 where
     T: PointDistance + 'a,
     Params: RTreeParams + 'a,
 {
-    node: &'a RTreeNode<T, Params>,
-    distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    pub(crate) node: &'a RTreeNode<T, Params>,
+    pub(crate) distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
 }
 
 impl<'a, T, Params> PartialEq for RTreeNodeDistanceWrapper<'a, T, Params>
@@ -0,0 +1,202 @@
+use bulk_load::{div_ceil, pack, sort_by_center};
+use envelope::Envelope;
+use node::{ParentNodeData, RTreeNode};
+use object::{PointDistance, RTreeObject};
+use params::RTreeParams;
+use point::Point;
+use rtree::RTree;
+use typenum::Unsigned;
+
+use std::thread;
+
+/// Like [`bulk_load`](::bulk_load::bulk_load), but packs the top-level STR
+/// slices concurrently instead of in one pass.
+///
+/// Requires the `parallel` feature. The input is sorted and divided into
+/// slices exactly as in the sequential algorithm, and each slice's leaf
+/// groups (each a parent of exactly one height) are built concurrently on
+/// their own thread, since that grouping pass is the bulk of the work and
+/// every slice's share of it is independent. Every level above the leaf
+/// groups is then packed by the same sequential [`pack`] used by
+/// `bulk_load`, over the combined list, so the whole tree gets a single
+/// uniform depth exactly as it would sequentially -- packing each slice's
+/// subtree independently instead would let differently-sized slices land at
+/// different depths and violate the leaves-all-at-the-same-height
+/// invariant. Worth it once a data set is large enough that leaf-group
+/// packing dominates over thread spawn and join overhead; for small inputs,
+/// prefer `bulk_load`.
+#[cfg(feature = "parallel")]
+pub fn par_bulk_load<T, Params>(elements: Vec<T>) -> RTree<T, Params>
+where
+    T: RTreeObject + Send + 'static,
+    Params: RTreeParams,
+{
+    let mut tree = RTree::new();
+    if elements.is_empty() {
+        return tree;
+    }
+    let size = elements.len();
+    let leaf_capacity = Params::MaxSize::to_usize();
+    let mut nodes: Vec<_> = elements.into_iter().map(RTreeNode::Leaf).collect();
+
+    if nodes.len() <= leaf_capacity {
+        *tree.root_mut() = ParentNodeData::new_parent(nodes);
+        tree.set_height(1);
+        tree.set_size(size);
+        return tree;
+    }
+
+    let dimensions = T::Point::dimensions();
+    let leaf_count = div_ceil(nodes.len(), leaf_capacity);
+    let slice_count = (leaf_count as f64).sqrt().ceil() as usize;
+    let slice_size = slice_count * leaf_capacity;
+
+    sort_by_center(&mut nodes, 0 % dimensions);
+
+    let handles: Vec<_> = nodes
+        .chunks(slice_size)
+        .map(|slice| slice.to_vec())
+        .map(|slice| {
+            thread::spawn(move || {
+                let mut slice = slice;
+                sort_by_center(&mut slice, 1 % dimensions);
+                slice
+                    .chunks(leaf_capacity)
+                    .map(|leaf_group| RTreeNode::Parent(ParentNodeData::new_parent(leaf_group.to_vec())))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let packed: Vec<_> = handles
+        .into_iter()
+        .flat_map(|handle| {
+            handle
+                .join()
+                .expect("par_bulk_load worker thread panicked")
+        })
+        .collect();
+
+    // `packed` holds the same uniform-height (all leaf groups) list the
+    // sequential algorithm would have built for this level; handing it to
+    // `pack` keeps every level above it identical to `bulk_load`'s.
+    let (root, height) = pack::<T, Params>(packed, leaf_capacity, 1 % dimensions);
+    *tree.root_mut() = root;
+    tree.set_height(height + 1);
+    tree.set_size(size);
+    tree
+}
+
+/// Looks up the nearest neighbor of every point in `query_points`, spreading
+/// the independent descents across a plain thread pool.
+///
+/// Requires the `parallel` feature. Each descent only reads `root`, so
+/// unlike [`par_bulk_load`] nothing needs to move between threads: `root`
+/// is borrowed for the scope's lifetime and every worker searches the same
+/// immutable tree with only `&self`-level access.
+#[cfg(feature = "parallel")]
+pub fn par_nearest_neighbor<'a, T, Params>(
+    root: &'a ParentNodeData<T, Params>,
+    query_points: &[<T::Envelope as Envelope>::Point],
+) -> Vec<Option<&'a T>>
+where
+    T: PointDistance + Sync,
+    Params: RTreeParams + Sync,
+{
+    if query_points.is_empty() {
+        return Vec::new();
+    }
+    let worker_count = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(query_points.len());
+    let chunk_size = div_ceil(query_points.len(), worker_count);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = query_points
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|query_point| ::nearest_neighbor::nearest_neighbor(root, query_point))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("par_nearest_neighbor worker thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{par_bulk_load, par_nearest_neighbor};
+    use node::RTreeNode;
+    use rtree::RTree;
+    use testutils::create_random_points;
+
+    /// The depth of every leaf under `node`, used to check that
+    /// `par_bulk_load` produces a tree with a single uniform height just
+    /// like the sequential `bulk_load`.
+    fn leaf_depths<T, Params>(node: &RTreeNode<T, Params>, depth: usize, depths: &mut Vec<usize>)
+    where
+        T: ::object::RTreeObject,
+        Params: ::params::RTreeParams,
+    {
+        match *node {
+            RTreeNode::Leaf(_) => depths.push(depth),
+            RTreeNode::Parent(ref data) => {
+                for child in &data.children {
+                    leaf_depths(child, depth + 1, depths);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_par_bulk_load_empty() {
+        let tree: RTree<[f32; 2]> = par_bulk_load(Vec::new());
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn test_par_bulk_load_leaves_at_uniform_depth() {
+        // Chosen so slicing leaves an uneven remainder, which is exactly the
+        // case that used to produce subtrees of mismatched height.
+        let points = create_random_points(2017, *b"parBulkLoadFrenz");
+        let tree: RTree<[f32; 2]> = par_bulk_load(points);
+
+        let mut depths = Vec::new();
+        for child in &tree.root().children {
+            leaf_depths(child, 1, &mut depths);
+        }
+        assert!(depths.iter().all(|&d| d == depths[0]));
+    }
+
+    #[test]
+    fn test_par_bulk_load_matches_brute_force() {
+        let points = create_random_points(2017, *b"parBulkLoadBrutF");
+        let tree: RTree<[f32; 2]> = par_bulk_load(points.clone());
+        assert_eq!(tree.size(), points.len());
+
+        let sample_points = create_random_points(50, *b"parBulkLoadQuery");
+        let results = par_nearest_neighbor(tree.root(), &sample_points);
+        for (sample_point, result) in sample_points.iter().zip(results) {
+            let mut nearest = None;
+            let mut closest_dist = ::std::f32::INFINITY;
+            for point in &points {
+                let delta = [point[0] - sample_point[0], point[1] - sample_point[1]];
+                let new_dist = delta[0] * delta[0] + delta[1] * delta[1];
+                if new_dist < closest_dist {
+                    closest_dist = new_dist;
+                    nearest = Some(point);
+                }
+            }
+            assert_eq!(nearest, result);
+        }
+    }
+}
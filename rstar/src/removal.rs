@@ -0,0 +1,255 @@
+use envelope::Envelope;
+use metrics::RTreeMetrics;
+use node::{mbr_for_children, ParentNodeData, RTreeNode};
+use object::RTreeObject;
+use params::RTreeParams;
+use rstar::{drive_insertions, DefaultSplitStrategy};
+use rtree::RTree;
+use typenum::Unsigned;
+
+/// Removes and returns the stored object equal to `t`, if any.
+///
+/// The matching leaf is located via an envelope-intersection descent (a
+/// subtree can only hold `t` if its envelope intersects `t`'s own
+/// envelope), after which every ancestor's envelope is recomputed bottom-up
+/// with `mbr_for_children`. A node left underflowing `Params::MinSize` by the
+/// removal is detached as a whole; its entries are forced back through the
+/// ordinary insertion path at their original heights, which may trigger
+/// further splits via the same `resolve_overflow` used by normal inserts,
+/// using whichever `SplitStrategy` the tree's `Params::DefaultInsertionStrategy`
+/// is configured with.
+pub fn remove<T, Params>(tree: &mut RTree<T, Params>, t: &T) -> Option<T>
+where
+    T: RTreeObject + PartialEq,
+    Params: RTreeParams,
+    Params::DefaultInsertionStrategy: DefaultSplitStrategy<T, Params>,
+{
+    let t_envelope = t.envelope();
+    remove_matching(
+        tree,
+        |envelope| envelope.intersects(&t_envelope),
+        |candidate| candidate == t,
+    )
+}
+
+/// Removes and returns the stored object whose envelope contains `point`, if
+/// any.
+pub fn remove_at<T, Params>(
+    tree: &mut RTree<T, Params>,
+    point: &<T::Envelope as Envelope>::Point,
+) -> Option<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+    Params::DefaultInsertionStrategy: DefaultSplitStrategy<T, Params>,
+{
+    remove_matching(
+        tree,
+        |envelope| envelope.contains_point(point),
+        |candidate| candidate.envelope().contains_point(point),
+    )
+}
+
+fn remove_matching<T, Params, C, F>(
+    tree: &mut RTree<T, Params>,
+    could_match: C,
+    predicate: F,
+) -> Option<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+    Params::DefaultInsertionStrategy: DefaultSplitStrategy<T, Params>,
+    C: Fn(&T::Envelope) -> bool,
+    F: Fn(&T) -> bool,
+{
+    if tree.size() == 0 {
+        return None;
+    }
+    let tree_height = tree.height();
+    let mut orphans = Vec::new();
+    let removed = remove_recursive(
+        tree.root_mut(),
+        tree_height - 1,
+        &could_match,
+        &predicate,
+        &mut orphans,
+    );
+    if removed.is_none() {
+        return None;
+    }
+    tree.set_size(tree.size() - 1);
+
+    // Shrink the tree if the root ends up with a single, still-Parent child.
+    while tree.height() > 1 && tree.root_mut().children.len() == 1 {
+        match tree.root_mut().children.pop().unwrap() {
+            RTreeNode::Parent(data) => {
+                let new_height = tree.height() - 1;
+                *tree.root_mut() = data;
+                tree.set_height(new_height);
+            }
+            leaf => {
+                tree.root_mut().children.push(leaf);
+                break;
+            }
+        }
+    }
+
+    if !orphans.is_empty() {
+        let mut metrics = RTreeMetrics::new();
+        drive_insertions::<T, Params, <Params::DefaultInsertionStrategy as DefaultSplitStrategy<T, Params>>::Split>(
+            tree, orphans, &mut metrics,
+        );
+    }
+    removed
+}
+
+/// Searches `node` (whose children are leaves once `remaining_height`
+/// reaches `0`) for an object matching `predicate`, recomputing `node`'s
+/// envelope and collecting the entries of any underflowing child into
+/// `orphans` as `(node, node_height, allow_reinsert)` triples ready for
+/// [`drive_insertions`].
+fn remove_recursive<T, Params, C, F>(
+    node: &mut ParentNodeData<T, Params>,
+    remaining_height: usize,
+    could_match: &C,
+    predicate: &F,
+    orphans: &mut Vec<(RTreeNode<T, Params>, usize, bool)>,
+) -> Option<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+    C: Fn(&T::Envelope) -> bool,
+    F: Fn(&T) -> bool,
+{
+    if remaining_height == 0 {
+        let index = node.children.iter().position(|child| match *child {
+            RTreeNode::Leaf(ref t) => could_match(&t.envelope()) && predicate(t),
+            RTreeNode::Parent(_) => false,
+        });
+        let removed = index.map(|index| match node.children.remove(index) {
+            RTreeNode::Leaf(t) => t,
+            RTreeNode::Parent(_) => unreachable!("leaves only appear at remaining_height == 0"),
+        });
+        if removed.is_some() {
+            node.envelope = mbr_for_children(&node.children);
+        }
+        return removed;
+    }
+
+    for index in 0..node.children.len() {
+        let could_descend = match node.children[index] {
+            RTreeNode::Parent(ref data) => could_match(&data.envelope),
+            RTreeNode::Leaf(_) => false,
+        };
+        if !could_descend {
+            continue;
+        }
+        let removed = match node.children[index] {
+            RTreeNode::Parent(ref mut data) => {
+                remove_recursive(data, remaining_height - 1, could_match, predicate, orphans)
+            }
+            RTreeNode::Leaf(_) => None,
+        };
+        if removed.is_none() {
+            continue;
+        }
+        let underflowed = match node.children[index] {
+            RTreeNode::Parent(ref data) => data.children.len() < Params::MinSize::to_usize(),
+            RTreeNode::Leaf(_) => false,
+        };
+        if underflowed {
+            if let RTreeNode::Parent(data) = node.children.remove(index) {
+                let orphan_height = remaining_height - 1;
+                orphans.extend(
+                    data.children
+                        .into_iter()
+                        .map(|child| (child, orphan_height, true)),
+                );
+            }
+        }
+        node.envelope = mbr_for_children(&node.children);
+        return removed;
+    }
+    None
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject + PartialEq,
+    Params: RTreeParams,
+    Params::DefaultInsertionStrategy: DefaultSplitStrategy<T, Params>,
+{
+    /// Removes and returns the stored object equal to `t`, if any; see
+    /// [`remove`] for details.
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        remove(self, t)
+    }
+
+    /// Removes and returns the stored object whose envelope contains
+    /// `point`, if any; see [`remove_at`] for details.
+    pub fn remove_at(&mut self, point: &<T::Envelope as Envelope>::Point) -> Option<T> {
+        remove_at(self, point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstar::HilbertRStarInsertionStrategy;
+    use rtree::RTree;
+    use testutils::create_random_points;
+    use typenum::{U2, U3, U6};
+
+    struct HilbertParams;
+
+    impl ::params::RTreeParams for HilbertParams {
+        type MinSize = U3;
+        type MaxSize = U6;
+        type ReinsertionCount = U2;
+        type DefaultInsertionStrategy = HilbertRStarInsertionStrategy;
+    }
+
+    #[test]
+    fn test_remove_missing_on_empty_tree() {
+        let mut tree: RTree<[f32; 2]> = RTree::new();
+        assert!(tree.remove(&[0.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn test_remove_updates_size_and_preserves_remaining_points() {
+        let mut points = create_random_points(300, *b"removalSizeFrenz");
+        let mut tree = RTree::new();
+        for p in &points {
+            tree.insert(*p);
+        }
+        assert_eq!(tree.size(), points.len());
+
+        while let Some(p) = points.pop() {
+            assert_eq!(tree.remove(&p), Some(p));
+            assert_eq!(tree.size(), points.len());
+            for remaining in &points {
+                assert_eq!(tree.nearest_neighbor(remaining), Some(remaining));
+            }
+        }
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn test_remove_with_hilbert_split_keeps_all_points_reachable() {
+        let mut points = create_random_points(300, *b"removalHilbertFr");
+        let mut tree: RTree<[f32; 2], HilbertParams> = RTree::new();
+        for p in &points {
+            tree.insert(*p);
+        }
+
+        // Remove half the points, forcing underflow-triggered reinsertion
+        // through the tree's Hilbert split strategy.
+        let to_remove: Vec<_> = points.split_off(points.len() / 2);
+        for p in &to_remove {
+            assert_eq!(tree.remove(p), Some(*p));
+        }
+        assert_eq!(tree.size(), points.len());
+        for p in &points {
+            assert_eq!(tree.nearest_neighbor(p), Some(p));
+        }
+    }
+}
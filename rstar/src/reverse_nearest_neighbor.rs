@@ -0,0 +1,208 @@
+use envelope::Envelope;
+use nearest_neighbor::RTreeNodeDistanceWrapper;
+use node::{ParentNodeData, RTreeNode};
+use num_traits::Zero;
+use object::PointDistance;
+use params::RTreeParams;
+use point::Point;
+use rtree::RTree;
+use std::collections::BinaryHeap;
+
+/// Returns every stored object `p` for which `query` is among `p`'s `k`
+/// nearest neighbors — the inverse of `nearest_neighbor`.
+///
+/// Candidates are proposed in order of increasing distance from `query` via
+/// a best-first descent. Before descending into a subtree, it is checked
+/// against [`is_dominated`]: once `k` already-confirmed reverse neighbors are
+/// each guaranteed closer than `query` to every point the subtree's envelope
+/// could contain, none of those points can still have `query` among their
+/// `k` nearest, so the whole subtree is skipped instead of walked leaf by
+/// leaf. Each surviving candidate `p` is confirmed by walking a
+/// `NearestNeighborIterator` around `p` and counting how many other stored
+/// objects are closer to `p` than `query` is, short-circuiting as soon as
+/// `k` closer objects are found.
+pub fn reverse_nearest_neighbors<'a, T, Params>(
+    root: &'a ParentNodeData<T, Params>,
+    query: &<T::Envelope as Envelope>::Point,
+    k: usize,
+) -> Vec<&'a T>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    let mut confirmed: Vec<&'a T> = Vec::new();
+    let mut heap = BinaryHeap::with_capacity(20);
+    extend_heap(&mut heap, &root.children, query);
+
+    while let Some(RTreeNodeDistanceWrapper { node, .. }) = heap.pop() {
+        match node {
+            RTreeNode::Parent(ref data) => {
+                if is_dominated(&data.envelope, query, &confirmed, k) {
+                    continue;
+                }
+                extend_heap(&mut heap, &data.children, query);
+            }
+            RTreeNode::Leaf(ref t) => {
+                if is_reverse_neighbor(root, t, query, k) {
+                    confirmed.push(t);
+                }
+            }
+        }
+    }
+    confirmed
+}
+
+fn extend_heap<'a, T, Params>(
+    heap: &mut BinaryHeap<RTreeNodeDistanceWrapper<'a, T, Params>>,
+    children: &'a [RTreeNode<T, Params>],
+    query: &<T::Envelope as Envelope>::Point,
+) where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    heap.extend(children.iter().map(|child| {
+        let distance = match child {
+            RTreeNode::Parent(ref data) => data.envelope.distance_2(query),
+            RTreeNode::Leaf(ref t) => t.distance_2(query),
+        };
+        RTreeNodeDistanceWrapper {
+            node: child,
+            distance: distance,
+        }
+    }));
+}
+
+/// True once `k` already-confirmed reverse neighbors are each guaranteed
+/// closer than `query` to every point `envelope` could contain, which means
+/// none of those points can still have `query` among their `k` nearest.
+fn is_dominated<T>(
+    envelope: &T::Envelope,
+    query: &<T::Envelope as Envelope>::Point,
+    confirmed: &[&T],
+    k: usize,
+) -> bool
+where
+    T: PointDistance,
+{
+    if confirmed.len() < k {
+        return false;
+    }
+    let min_dist_to_query = envelope.distance_2(query);
+    let dominators = confirmed
+        .iter()
+        .filter(|c| max_dist_2(envelope, &c.envelope().center()) < min_dist_to_query)
+        .count();
+    dominators >= k
+}
+
+/// The squared distance from `point` to the farthest point `envelope` could
+/// contain, found by picking whichever corner is farther from `point` along
+/// each axis independently.
+fn max_dist_2<E>(envelope: &E, point: &E::Point) -> <E::Point as Point>::Scalar
+where
+    E: Envelope,
+{
+    let (lower, upper) = (envelope.lower(), envelope.upper());
+    let mut sum = Zero::zero();
+    for axis in 0..E::Point::dimensions() {
+        let to_lower = point.nth(axis) - lower.nth(axis);
+        let to_upper = point.nth(axis) - upper.nth(axis);
+        let farthest = if to_lower * to_lower > to_upper * to_upper {
+            to_lower
+        } else {
+            to_upper
+        };
+        sum = sum + farthest * farthest;
+    }
+    sum
+}
+
+fn is_reverse_neighbor<'a, T, Params>(
+    root: &'a ParentNodeData<T, Params>,
+    candidate: &T,
+    query: &<T::Envelope as Envelope>::Point,
+    k: usize,
+) -> bool
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    let candidate_center = candidate.envelope().center();
+    let query_distance = candidate.distance_2(query);
+    let mut closer_count = 0;
+    for neighbor in ::nearest_neighbor::NearestNeighborIterator::new(root, &candidate_center) {
+        if neighbor as *const _ == candidate as *const _ {
+            // Skip the candidate itself; it is always in its own tree.
+            continue;
+        }
+        if neighbor.distance_2(&candidate_center) >= query_distance {
+            // Everything from here on is at least as far from `candidate`
+            // as `query` is, so it can no longer count against `k`.
+            break;
+        }
+        closer_count += 1;
+        if closer_count >= k {
+            return false;
+        }
+    }
+    true
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    /// Returns every stored object `p` for which `query` is among `p`'s `k`
+    /// nearest neighbors; see [`reverse_nearest_neighbors`] for details.
+    pub fn reverse_nearest_neighbors(
+        &self,
+        query: &<T::Envelope as Envelope>::Point,
+        k: usize,
+    ) -> Vec<&T> {
+        reverse_nearest_neighbors(self.root(), query, k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use object::PointDistance;
+    use rtree::RTree;
+    use testutils::create_random_points;
+
+    #[test]
+    fn test_reverse_nearest_neighbors_empty() {
+        let tree: RTree<[f32; 2]> = RTree::new();
+        assert!(tree.reverse_nearest_neighbors(&[0.0, 0.0], 1).is_empty());
+    }
+
+    #[test]
+    fn test_reverse_nearest_neighbors_matches_brute_force() {
+        let points = create_random_points(300, *b"rknnBruteForceFr");
+        let mut tree = RTree::new();
+        for p in &points {
+            tree.insert(*p);
+        }
+        let k = 3;
+        for query in &points {
+            let mut expected: Vec<_> = points
+                .iter()
+                .filter(|p| {
+                    let query_distance = p.distance_2(query);
+                    let closer_count = points
+                        .iter()
+                        .filter(|other| {
+                            *other as *const _ != *p as *const _
+                                && other.distance_2(&p.envelope().center()) < query_distance
+                        })
+                        .count();
+                    closer_count < k
+                })
+                .collect();
+            let mut actual = tree.reverse_nearest_neighbors(query, k);
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            actual.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(expected, actual);
+        }
+    }
+}
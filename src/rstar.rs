@@ -3,14 +3,22 @@ use ::node::{ParentNodeData, RTreeNode, mbr_for_children};
 use point::{Point, PointExt};
 use params::RTreeParams;
 use object::RTreeObject;
-use num_traits::{Zero, Bounded};
+use num_traits::{Zero, Bounded, NumCast};
 use typenum::Unsigned;
 use metrics::RTreeMetrics;
 use envelope::Envelope;
 
 pub enum RStarInsertionStrategy { }
 
-enum InsertionResult<T, Params>
+/// Like [`RStarInsertionStrategy`], but splits overflowing nodes by sorting
+/// their children along a Hilbert curve instead of searching for the best
+/// split axis; the minimum-overlap cut point is then chosen the same way for
+/// both. Cheaper to compute and tends to cluster children
+/// that are close in space, which improves query locality at some cost to
+/// packing quality. Select it via `RTreeParams::DefaultInsertionStrategy`.
+pub enum HilbertRStarInsertionStrategy { }
+
+pub(crate) enum InsertionResult<T, Params>
     where T: RTreeObject,
           Params: RTreeParams
 {
@@ -22,77 +30,111 @@ enum InsertionResult<T, Params>
 impl InsertionStrategy for RStarInsertionStrategy {
     fn insert<T, Params>(tree: &mut RTree<T, Params>,
                          t: T,
-                         metrics: &mut RTreeMetrics) 
+                         metrics: &mut RTreeMetrics)
         where Params: RTreeParams,
               T: RTreeObject,
     {
-        metrics.increment_insertions();
-        if tree.size() == 0 {
-            // The root won't be split - adjust the height manually
-            tree.set_height(1);
-        }
-        let mut tree_height = tree.height();
-
-        let mut insertion_stack = vec![(RTreeNode::Leaf(t), 0, true)];
-
-        let mut reinsertions = Vec::with_capacity(tree_height);
-        reinsertions.resize(tree_height, true);
-
-        while let Some((next, node_height, can_reinsert)) = insertion_stack.pop() {
-            match recursive_insert(tree.root_mut(),
-                                   next,
-                                   tree_height - node_height - 1,
-                                   can_reinsert,
-                                   metrics) {
-                InsertionResult::Split(node) => {
-                    // The root node was split, create a new root and increase height
-                    tree_height += 1;
-                    let old_root = ::std::mem::replace(
-                        tree.root_mut(), ParentNodeData::new_root());
-                    tree.set_height(tree_height);
-                    let new_mbr = old_root.mbr.merged(&node.mbr());
-                    tree.root_mut().mbr = new_mbr;
-                    tree.root_mut().children.push(RTreeNode::Parent(old_root));
-                    tree.root_mut().children.push(node);
-                },
-                InsertionResult::Reinsert(nodes, height) => {
-                    let node_height = tree_height - height - 1;
-                    let can_reinsert = reinsertions[node_height];
-                    reinsertions[node_height] = false;
-                    // Schedule elements for reinsertion
-                    insertion_stack.extend(nodes.into_iter().map(|n| (n, node_height, can_reinsert)));
-                },
-                InsertionResult::Complete => (),
-            }
+        insert_with_split::<T, Params, AxisSplit>(tree, t, metrics)
+    }
+}
+
+impl InsertionStrategy for HilbertRStarInsertionStrategy {
+    fn insert<T, Params>(tree: &mut RTree<T, Params>,
+                         t: T,
+                         metrics: &mut RTreeMetrics)
+        where Params: RTreeParams,
+              T: RTreeObject,
+    {
+        insert_with_split::<T, Params, HilbertSplit>(tree, t, metrics)
+    }
+}
+
+pub(crate) fn insert_with_split<T, Params, S>(tree: &mut RTree<T, Params>,
+                                    t: T,
+                                    metrics: &mut RTreeMetrics)
+    where Params: RTreeParams,
+          T: RTreeObject,
+          S: SplitStrategy<T, Params>,
+{
+    metrics.increment_insertions();
+    if tree.size() == 0 {
+        // The root won't be split - adjust the height manually
+        tree.set_height(1);
+    }
+    drive_insertions::<T, Params, S>(tree, vec![(RTreeNode::Leaf(t), 0, true)], metrics)
+}
+
+/// Drains `insertion_stack`, inserting each `(node, node_height, can_reinsert)`
+/// entry at its target height and growing the root on overflow. Used both
+/// for ordinary single-object insertion and to re-home the orphans produced
+/// by [`::removal::remove`] at their original heights.
+pub(crate) fn drive_insertions<T, Params, S>(tree: &mut RTree<T, Params>,
+                                    mut insertion_stack: Vec<(RTreeNode<T, Params>, usize, bool)>,
+                                    metrics: &mut RTreeMetrics)
+    where Params: RTreeParams,
+          T: RTreeObject,
+          S: SplitStrategy<T, Params>,
+{
+    let mut tree_height = tree.height();
+
+    let mut reinsertions = Vec::with_capacity(tree_height);
+    reinsertions.resize(tree_height, true);
+
+    while let Some((next, node_height, can_reinsert)) = insertion_stack.pop() {
+        match recursive_insert::<T, Params, S>(tree.root_mut(),
+                               next,
+                               tree_height - node_height - 1,
+                               can_reinsert,
+                               metrics) {
+            InsertionResult::Split(node) => {
+                // The root node was split, create a new root and increase height
+                tree_height += 1;
+                let old_root = ::std::mem::replace(
+                    tree.root_mut(), ParentNodeData::new_root());
+                tree.set_height(tree_height);
+                let new_mbr = old_root.mbr.merged(&node.mbr());
+                tree.root_mut().mbr = new_mbr;
+                tree.root_mut().children.push(RTreeNode::Parent(old_root));
+                tree.root_mut().children.push(node);
+            },
+            InsertionResult::Reinsert(nodes, height) => {
+                let node_height = tree_height - height - 1;
+                let can_reinsert = reinsertions[node_height];
+                reinsertions[node_height] = false;
+                // Schedule elements for reinsertion
+                insertion_stack.extend(nodes.into_iter().map(|n| (n, node_height, can_reinsert)));
+            },
+            InsertionResult::Complete => (),
         }
     }
 }
 
-fn recursive_insert<T, Params>(node: &mut ParentNodeData<T, Params>, 
-                               t: RTreeNode<T, Params>, 
+pub(crate) fn recursive_insert<T, Params, S>(node: &mut ParentNodeData<T, Params>,
+                               t: RTreeNode<T, Params>,
                                target_height: usize,
                                allow_reinsert: bool,
                                metrics: &mut RTreeMetrics) -> InsertionResult<T, Params>
     where Params: RTreeParams,
           T: RTreeObject,
+          S: SplitStrategy<T, Params>,
 {
     metrics.increment_recursive_insertions();
     node.mbr.merge(&t.mbr());
     if target_height == 0 {
         // Force insertion into this node
         node.children.push(t);
-        return resolve_overflow(node, allow_reinsert, metrics);
+        return resolve_overflow::<T, Params, S>(node, allow_reinsert, metrics);
     }
-    let expand = { 
+    let expand = {
         let all_leaves = target_height == 1;
         let follow = choose_subtree(node, &t, all_leaves, metrics);
-        recursive_insert(follow, t, target_height - 1, allow_reinsert, metrics)
+        recursive_insert::<T, Params, S>(follow, t, target_height - 1, allow_reinsert, metrics)
     };
     match expand {
         InsertionResult::Split(child) => {
             node.mbr.merge(&child.mbr());
             node.children.push(child);
-            resolve_overflow(node, allow_reinsert, metrics)
+            resolve_overflow::<T, Params, S>(node, allow_reinsert, metrics)
         },
         InsertionResult::Reinsert(reinsertion_nodes, height) => {
             node.mbr = mbr_for_children(&node.children);
@@ -174,11 +216,12 @@ fn choose_subtree<'a, 'b, T, Params>(node: &'a mut ParentNodeData<T, Params>,
     }
 }
 
-fn resolve_overflow<T, Params>(node: &mut ParentNodeData<T, Params>,
+pub(crate) fn resolve_overflow<T, Params, S>(node: &mut ParentNodeData<T, Params>,
                                allow_reinsert: bool,
-                               metrics: &mut RTreeMetrics) -> InsertionResult<T, Params> 
+                               metrics: &mut RTreeMetrics) -> InsertionResult<T, Params>
     where T: RTreeObject,
-          Params: RTreeParams
+          Params: RTreeParams,
+          S: SplitStrategy<T, Params>,
 {
     metrics.increment_resolve_overflow();
     if node.children.len() > Params::MaxSize::to_usize() {
@@ -186,7 +229,7 @@ fn resolve_overflow<T, Params>(node: &mut ParentNodeData<T, Params>,
         let reinsertion_count = Params::ReinsertionCount::to_usize();
         if reinsertion_count == 0 || !allow_reinsert {
             // We did already reinsert on that level - split this node
-            let offsplit = split(node, metrics);
+            let offsplit = S::split(node, metrics);
             InsertionResult::Split(offsplit)
         } else {
             // We didn't attempt to reinsert yet - give it a try
@@ -198,16 +241,85 @@ fn resolve_overflow<T, Params>(node: &mut ParentNodeData<T, Params>,
     }
 }
 
-fn split<T, Params>(node: &mut ParentNodeData<T, Params>, metrics: &mut RTreeMetrics) -> RTreeNode<T, Params> 
+/// Orders an overflowing node's children before the minimum-overlap cut
+/// point is chosen. `RStarInsertionStrategy` and `HilbertRStarInsertionStrategy`
+/// each provide one, differing only in how the children are ordered prior to
+/// `finish_split`.
+pub(crate) trait SplitStrategy<T, Params>
+    where T: RTreeObject,
+          Params: RTreeParams,
+{
+    fn split(node: &mut ParentNodeData<T, Params>, metrics: &mut RTreeMetrics) -> RTreeNode<T, Params>;
+}
+
+/// Maps an [`InsertionStrategy`] to the [`SplitStrategy`] it splits
+/// overflowing nodes with, so code that only knows a tree's configured
+/// `Params::DefaultInsertionStrategy` (e.g. forced reinsertion during
+/// [`::removal::remove`]) can still split with the right algorithm instead
+/// of hardcoding one.
+pub(crate) trait DefaultSplitStrategy<T, Params>
+    where T: RTreeObject,
+          Params: RTreeParams,
+{
+    type Split: SplitStrategy<T, Params>;
+}
+
+impl<T, Params> DefaultSplitStrategy<T, Params> for RStarInsertionStrategy
+    where T: RTreeObject,
+          Params: RTreeParams,
+{
+    type Split = AxisSplit;
+}
+
+impl<T, Params> DefaultSplitStrategy<T, Params> for HilbertRStarInsertionStrategy
+    where T: RTreeObject,
+          Params: RTreeParams,
+{
+    type Split = HilbertSplit;
+}
+
+pub(crate) enum AxisSplit { }
+
+impl<T, Params> SplitStrategy<T, Params> for AxisSplit
+    where T: RTreeObject,
+          Params: RTreeParams,
+{
+    fn split(node: &mut ParentNodeData<T, Params>, metrics: &mut RTreeMetrics) -> RTreeNode<T, Params> {
+        metrics.increment_splits();
+        let axis = get_split_axis(node);
+        debug_assert!(node.children.len() >= 2);
+        // Sort along axis
+        T::Envelope::align_envelopes(axis, &mut node.children, |c| c.mbr());
+        finish_split(node)
+    }
+}
+
+pub(crate) enum HilbertSplit { }
+
+impl<T, Params> SplitStrategy<T, Params> for HilbertSplit
+    where T: RTreeObject,
+          Params: RTreeParams,
+{
+    fn split(node: &mut ParentNodeData<T, Params>, metrics: &mut RTreeMetrics) -> RTreeNode<T, Params> {
+        metrics.increment_splits();
+        debug_assert!(node.children.len() >= 2);
+        // Sort by the Hilbert index of each child's MBR center, using the
+        // node's own MBR to normalize coordinates onto the curve's grid.
+        let mbr = node.mbr.clone();
+        node.children.sort_by_key(|child| hilbert_index(&mbr, &child.mbr().center()));
+        finish_split(node)
+    }
+}
+
+/// Chooses the cut position in `[MinSize, len - MinSize]` that minimizes
+/// overlap (then area) between the two resulting MBRs, and performs the
+/// split. Shared by every `SplitStrategy`, which only differ in how
+/// `node.children` is ordered beforehand.
+fn finish_split<T, Params>(node: &mut ParentNodeData<T, Params>) -> RTreeNode<T, Params>
     where T: RTreeObject,
           Params: RTreeParams
 {
-    metrics.increment_splits();
-    let axis = get_split_axis(node);
     let zero = <T::Point as Point>::Scalar::zero();
-    debug_assert!(node.children.len() >= 2);
-    // Sort along axis
-    T::Envelope::align_envelopes(axis, &mut node.children, |c| c.mbr());
     let mut best = (zero, zero);
     let min_size = Params::MinSize::to_usize();
     let mut best_index = min_size;
@@ -234,10 +346,55 @@ fn split<T, Params>(node: &mut ParentNodeData<T, Params>, metrics: &mut RTreeMet
     let offsplit = node.children.split_off(best_index);
     node.mbr = mbr_for_children(&node.children);
     let result = RTreeNode::Parent(ParentNodeData::new_parent(offsplit));
-    
+
     result
 }
 
+/// Order of the Hilbert curve grid (2^`HILBERT_ORDER` cells per axis) used to
+/// quantize MBR centers before computing their curve index. Only the first
+/// two axes of `T::Point` are used, matching the classic 2D Hilbert mapping.
+const HILBERT_ORDER: u32 = 16;
+
+fn hilbert_index<E>(mbr: &E, center: &E::Point) -> u64
+    where E: Envelope
+{
+    let side = 1u64 << HILBERT_ORDER;
+    let mut x = quantize_axis(mbr, center, 0, side);
+    let mut y = quantize_axis(mbr, center, 1, side);
+    let mut d = 0u64;
+    let mut s = side / 2;
+    while s > 0 {
+        let rx: u64 = if (x & s) > 0 { 1 } else { 0 };
+        let ry: u64 = if (y & s) > 0 { 1 } else { 0 };
+        d += s * s * ((3 * rx) ^ ry);
+        // Rotate (and, in the bottom-right quadrant, flip) so the next level's
+        // sub-square lines up with the curve's orientation.
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            ::std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
+
+fn quantize_axis<E>(mbr: &E, center: &E::Point, axis: usize, side: u64) -> u64
+    where E: Envelope
+{
+    let lower = mbr.lower().nth(axis);
+    let upper = mbr.upper().nth(axis);
+    let span = upper - lower;
+    if span <= Zero::zero() {
+        return 0;
+    }
+    let fraction = (center.nth(axis) - lower) / span;
+    let scale: <E::Point as Point>::Scalar = NumCast::from(side - 1).unwrap();
+    NumCast::from(fraction * scale).unwrap_or(0)
+}
+
 fn get_split_axis<T, Params>(node: &mut ParentNodeData<T, Params>) -> usize 
     where T: RTreeObject,
       Params: RTreeParams
@@ -291,4 +448,51 @@ fn reinsert<T, Params>(node: &mut ParentNodeData<T, Params>,
     let result = node.children.split_off(num_children - Params::ReinsertionCount::to_usize());
     node.mbr = mbr_for_children(&node.children);
     result
+}
+
+#[cfg(test)]
+mod test {
+    use super::HilbertRStarInsertionStrategy;
+    use params::RTreeParams;
+    use rtree::RTree;
+    use testutils::create_random_points;
+    use typenum::{U2, U3, U6};
+
+    struct HilbertParams;
+
+    impl RTreeParams for HilbertParams {
+        type MinSize = U3;
+        type MaxSize = U6;
+        type ReinsertionCount = U2;
+        type DefaultInsertionStrategy = HilbertRStarInsertionStrategy;
+    }
+
+    #[test]
+    fn test_hilbert_split_empty() {
+        let tree: RTree<[f32; 2], HilbertParams> = RTree::new();
+        assert!(tree.nearest_neighbor(&[0.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn test_hilbert_split_matches_brute_force() {
+        let points = create_random_points(1000, *b"hilbertCurveFren");
+        let mut tree: RTree<[f32; 2], HilbertParams> = RTree::new();
+        for p in &points {
+            tree.insert(*p);
+        }
+        let sample_points = create_random_points(100, *b"quantizeTheGridN");
+        for sample_point in &sample_points {
+            let mut nearest = None;
+            let mut closest_dist = ::std::f32::INFINITY;
+            for point in &points {
+                let delta = [point[0] - sample_point[0], point[1] - sample_point[1]];
+                let new_dist = delta[0] * delta[0] + delta[1] * delta[1];
+                if new_dist < closest_dist {
+                    closest_dist = new_dist;
+                    nearest = Some(point);
+                }
+            }
+            assert_eq!(nearest, tree.nearest_neighbor(sample_point));
+        }
+    }
 }
\ No newline at end of file